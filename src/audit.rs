@@ -0,0 +1,70 @@
+//! A structured audit hook observing every authorization decision
+//! [`RestrictingVfs`](crate::RestrictingVfs) makes, for intrusion detection and
+//! compliance logging.
+
+use crate::VfsOperations;
+use std::path::PathBuf;
+
+/// Whether an operation was allowed or denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The operation was allowed.
+    Allowed,
+    /// The operation was denied.
+    Denied,
+}
+
+/// A single authorization decision, as reported to an [`AuditSink`].
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    /// The user the decision was made for.
+    pub username: String,
+    /// The operation that was attempted.
+    pub operation: VfsOperations,
+    /// The target path(s) of the operation (two for `rename`, one otherwise).
+    pub paths: Vec<PathBuf>,
+    /// Whether the operation was allowed or denied.
+    pub outcome: Outcome,
+}
+
+/// Observes the authorization decisions made by a [`RestrictingVfs`](crate::RestrictingVfs).
+///
+/// Install an implementation with
+/// [`RestrictingVfs::with_audit`](crate::RestrictingVfs::with_audit); the default
+/// is [`NoopAuditSink`], which observes nothing.
+pub trait AuditSink: Send + Sync {
+    /// Called once per authorization decision, right after it's made.
+    fn record(&self, event: AuditEvent);
+}
+
+/// An [`AuditSink`] that discards every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopAuditSink;
+
+impl AuditSink for NoopAuditSink {
+    fn record(&self, _event: AuditEvent) {}
+}
+
+/// An [`AuditSink`] that logs every event via the [`log`](https://docs.rs/log)
+/// crate: allowed operations at `info`, denied ones at `warn`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingAuditSink;
+
+impl AuditSink for LoggingAuditSink {
+    fn record(&self, event: AuditEvent) {
+        match event.outcome {
+            Outcome::Allowed => log::info!(
+                "allowed {:?} by {:?} on {:?}",
+                event.operation,
+                event.username,
+                event.paths
+            ),
+            Outcome::Denied => log::warn!(
+                "denied {:?} by {:?} on {:?}",
+                event.operation,
+                event.username,
+                event.paths
+            ),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Support for `SITE CHMOD`, gated on the [`VfsOperations::MODE`](crate::VfsOperations::MODE)
+//! permission bit.
+
+use async_trait::async_trait;
+use libunftp::storage::{self, StorageBackend};
+use std::fmt::Debug;
+use std::path::Path;
+
+/// Implemented by delegate storage back-ends that can change a file's POSIX
+/// permissions, mirroring the `SITE CHMOD` support added upstream in libunftp.
+///
+/// [`RestrictingVfs::set_permissions`](crate::RestrictingVfs::set_permissions) is
+/// only available when `Delegate` implements this.
+#[async_trait]
+pub trait ChmodCapable<User>: StorageBackend<User> {
+    /// Sets the POSIX file mode of `path` to `mode`.
+    async fn chmod<P: AsRef<Path> + Send + Debug>(&self, user: &User, path: P, mode: u32) -> storage::Result<()>;
+}
+
+/// Parses a `SITE CHMOD` mode specification, accepting both octal (`644`,
+/// `0644`) and symbolic (`rwxr-x---`) forms, using the same bitmask parsing as
+/// the [`file-mode`](https://docs.rs/file-mode) crate.
+///
+/// A Rust-style `0o` (or `0O`) prefix, as FTP clients sometimes send, is
+/// stripped before parsing since [`file_mode::Mode`]'s `FromStr` doesn't
+/// recognize it; a hex (`0x`) spec is not supported.
+pub fn parse_mode(spec: &str) -> Result<u32, file_mode::ModeParseError> {
+    let spec = spec.strip_prefix("0o").or_else(|| spec.strip_prefix("0O")).unwrap_or(spec);
+    spec.parse::<file_mode::Mode>().map(|mode| mode.mode())
+}
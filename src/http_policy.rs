@@ -0,0 +1,284 @@
+//! Optional support for resolving permissions from an external HTTP policy
+//! service rather than baking them into a [`UserWithPermissions`](crate::UserWithPermissions)
+//! implementation, mirroring the HTTP user-detail extensions elsewhere in the
+//! unFTP ecosystem. Enabled by the `http-policy` feature.
+
+use crate::{PathRules, UserWithPermissions, VfsOperations};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// What [`CachedPermissionProvider`] should do when the policy service can't be
+/// reached (connection error, timeout, non-success status, bad JSON).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Deny every operation for the user until the service is reachable again.
+    FailClosed,
+    /// Fall back to the user's local [`UserWithPermissions`](crate::UserWithPermissions)
+    /// permissions until the service is reachable again.
+    FailOpen,
+}
+
+/// Permissions for a single user, as resolved from the policy service.
+#[derive(Debug, Clone)]
+pub struct RemotePermissions {
+    /// The flat operation mask granted to the user.
+    pub permissions: VfsOperations,
+    /// Optional path-scoped overrides, resolved the same way as a local [`PathRules`].
+    pub rules: Option<PathRules>,
+}
+
+/// Marks [`UserWithPermissions`](crate::UserWithPermissions) implementors that
+/// [`CachedPermissionProvider`] can query and cache by
+/// [`username`](UserWithPermissions::username).
+///
+/// Reuses [`UserWithPermissions::username`](crate::UserWithPermissions::username)
+/// rather than defining its own accessor, so a user type doesn't need to
+/// implement two identically-named methods (and callers don't hit an
+/// ambiguous-method error) to be authorized via [`HttpPermissionProvider`].
+/// Blanket-implemented for every [`UserWithPermissions`](crate::UserWithPermissions);
+/// override [`username`](UserWithPermissions::username) there to give it a
+/// stable remote key.
+pub trait NamedUser: UserWithPermissions {}
+
+impl<T: UserWithPermissions> NamedUser for T {}
+
+#[derive(serde::Deserialize)]
+struct PolicyResponse {
+    permissions: Vec<String>,
+    #[serde(default)]
+    rules: Vec<PolicyRuleDto>,
+}
+
+#[derive(serde::Deserialize)]
+struct PolicyRuleDto {
+    pattern: String,
+    permissions: Vec<String>,
+}
+
+/// The policy service named an operation this crate doesn't recognize.
+///
+/// Surfaced instead of silently dropping the token, so a typo or a
+/// not-yet-supported operation name in a policy response is visible rather
+/// than quietly granting less access than configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownOperation(
+    /// The unrecognized operation name, as sent by the policy service.
+    pub String,
+);
+
+impl std::fmt::Display for UnknownOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown operation in policy response: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownOperation {}
+
+/// An error resolving permissions from the policy service.
+#[derive(Debug)]
+pub enum PolicyError {
+    /// The HTTP request failed, or the response couldn't be parsed as JSON.
+    Request(reqwest::Error),
+    /// The response named an operation this crate doesn't recognize.
+    UnknownOperation(UnknownOperation),
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::Request(e) => write!(f, "policy service request failed: {e}"),
+            PolicyError::UnknownOperation(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<reqwest::Error> for PolicyError {
+    fn from(e: reqwest::Error) -> Self {
+        PolicyError::Request(e)
+    }
+}
+
+impl From<UnknownOperation> for PolicyError {
+    fn from(e: UnknownOperation) -> Self {
+        PolicyError::UnknownOperation(e)
+    }
+}
+
+fn op_from_name(name: &str) -> Result<VfsOperations, UnknownOperation> {
+    Ok(match name {
+        "MK_DIR" => VfsOperations::MK_DIR,
+        "RM_DIR" => VfsOperations::RM_DIR,
+        "GET" => VfsOperations::GET,
+        "PUT" => VfsOperations::PUT,
+        "DEL" => VfsOperations::DEL,
+        "RENAME" => VfsOperations::RENAME,
+        "MD5" => VfsOperations::MD5,
+        "LIST" => VfsOperations::LIST,
+        "CWD" => VfsOperations::CWD,
+        "STAT" => VfsOperations::STAT,
+        "MODE" => VfsOperations::MODE,
+        "READ_OPS" => VfsOperations::READ_OPS,
+        "WRITE_OPS" => VfsOperations::WRITE_OPS,
+        other => return Err(UnknownOperation(other.to_string())),
+    })
+}
+
+fn parse_ops(names: &[String]) -> Result<VfsOperations, UnknownOperation> {
+    names
+        .iter()
+        .try_fold(VfsOperations::empty(), |acc, name| op_from_name(name).map(|op| acc | op))
+}
+
+/// Resolves a user's [`VfsOperations`] (and, if present, [`PathRules`]) from an
+/// external HTTP policy service instead of requiring it baked into the
+/// [`UserDetail`](libunftp::auth::UserDetail) implementation.
+///
+/// By default issues `GET {base_url}/{username}`; call
+/// [`with_post`](HttpPermissionProvider::with_post) to `POST {base_url}` with
+/// `{"username": ...}` instead. Either way the service is expected to respond
+/// with:
+/// ```json
+/// {"permissions": ["GET", "LIST", "PUT"], "rules": [{"pattern": "/incoming/**", "permissions": ["PUT"]}]}
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpPermissionProvider {
+    base_url: String,
+    client: reqwest::Client,
+    use_post: bool,
+}
+
+impl HttpPermissionProvider {
+    /// Creates a provider that issues `GET {base_url}/{username}` requests.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        HttpPermissionProvider {
+            base_url: base_url.into(),
+            client: reqwest::Client::new(),
+            use_post: false,
+        }
+    }
+
+    /// Issues `POST {base_url}` with a `{"username": ...}` body instead of a `GET`.
+    pub fn with_post(mut self) -> Self {
+        self.use_post = true;
+        self
+    }
+
+    /// Fetches and parses the permissions for `username`.
+    pub async fn fetch(&self, username: &str) -> Result<RemotePermissions, PolicyError> {
+        let response = if self.use_post {
+            self.client
+                .post(&self.base_url)
+                .json(&serde_json::json!({ "username": username }))
+                .send()
+                .await?
+        } else {
+            self.client
+                .get(format!("{}/{}", self.base_url, username))
+                .send()
+                .await?
+        };
+        let body: PolicyResponse = response.error_for_status()?.json().await?;
+        let permissions = parse_ops(&body.permissions)?;
+        let rules = if body.rules.is_empty() {
+            None
+        } else {
+            let mut path_rules = PathRules::new(permissions);
+            for rule in &body.rules {
+                path_rules = path_rules.rule(&rule.pattern, parse_ops(&rule.permissions)?);
+            }
+            Some(path_rules)
+        };
+        Ok(RemotePermissions { permissions, rules })
+    }
+}
+
+struct CacheEntry {
+    permissions: RemotePermissions,
+    fetched_at: Instant,
+}
+
+/// Wraps an [`HttpPermissionProvider`] with a TTL-based in-memory cache keyed by
+/// username, so a network round-trip isn't needed for every FTP command, plus a
+/// configurable [`FailurePolicy`] for when the service is unreachable.
+pub struct CachedPermissionProvider {
+    provider: HttpPermissionProvider,
+    ttl: Duration,
+    failure_policy: FailurePolicy,
+    cache: RwLock<HashMap<String, CacheEntry>>,
+}
+
+impl CachedPermissionProvider {
+    /// Wraps `provider`, caching each user's permissions for `ttl` and applying
+    /// `failure_policy` when the service can't be reached.
+    pub fn new(provider: HttpPermissionProvider, ttl: Duration, failure_policy: FailurePolicy) -> Self {
+        CachedPermissionProvider {
+            provider,
+            ttl,
+            failure_policy,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the permissions for `username`, serving from the cache when the
+    /// entry is still within its TTL and refreshing from the policy service
+    /// otherwise. Returns `None` only when the service is unreachable and
+    /// `failure_policy` is [`FailurePolicy::FailOpen`], signalling the caller to
+    /// fall back to the user's local permissions.
+    pub async fn resolve(&self, username: &str) -> Option<RemotePermissions> {
+        if let Some(entry) = self.cache.read().await.get(username) {
+            if entry.fetched_at.elapsed() < self.ttl {
+                return Some(entry.permissions.clone());
+            }
+        }
+        match self.provider.fetch(username).await {
+            Ok(permissions) => {
+                self.cache.write().await.insert(
+                    username.to_string(),
+                    CacheEntry {
+                        permissions: permissions.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Some(permissions)
+            }
+            Err(_) => match self.failure_policy {
+                FailurePolicy::FailClosed => Some(RemotePermissions {
+                    permissions: VfsOperations::empty(),
+                    rules: None,
+                }),
+                FailurePolicy::FailOpen => None,
+            },
+        }
+    }
+}
+
+/// Object-safe indirection letting [`DefaultPolicy`](crate::DefaultPolicy) hold a
+/// remote permission source without becoming generic over the provider type.
+#[async_trait]
+pub trait RemoteProvider<User>: Send + Sync {
+    /// Resolves the remote permissions for `user`, or `None` to fall back to the
+    /// user's local permissions (see [`FailurePolicy::FailOpen`]).
+    async fn resolve_for(&self, user: &User) -> Option<RemotePermissions>;
+}
+
+#[async_trait]
+impl<User: NamedUser + Sync> RemoteProvider<User> for CachedPermissionProvider {
+    async fn resolve_for(&self, user: &User) -> Option<RemotePermissions> {
+        self.resolve(user.username()).await
+    }
+}
+
+/// Wraps the `Arc<dyn RemoteProvider<User>>` held by [`DefaultPolicy`](crate::DefaultPolicy),
+/// giving it a `Debug` impl regardless of which provider is plugged in.
+pub(crate) struct RemoteProviderHandle<User>(pub(crate) Arc<dyn RemoteProvider<User>>);
+
+impl<User> std::fmt::Debug for RemoteProviderHandle<User> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RemoteProviderHandle(..)")
+    }
+}
@@ -33,6 +33,8 @@
 //!     }
 //! }
 //!
+//! // UserDetail requires Display; UserWithPermissions no longer does, but
+//! // this impl still needs one to satisfy UserDetail's supertrait bound.
 //! impl std::fmt::Display for User {
 //!     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 //!         write!(f, "User(username: {:?}", self.username,)
@@ -43,6 +45,10 @@
 //!     fn permissions(&self) -> VfsOperations {
 //!         self.permissions
 //!     }
+//!
+//!     fn username(&self) -> &str {
+//!         &self.username
+//!     }
 //! }
 //!
 //! // Return type omited for brevity.
@@ -67,6 +73,30 @@ use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use tokio::io::AsyncRead;
 
+mod path_rules;
+
+pub use path_rules::PathRules;
+
+#[cfg(feature = "http-policy")]
+mod http_policy;
+
+#[cfg(feature = "http-policy")]
+pub use http_policy::{
+    CachedPermissionProvider, FailurePolicy, HttpPermissionProvider, NamedUser, PolicyError, RemotePermissions,
+};
+
+mod policy;
+
+pub use policy::{AuthorizationPolicy, DefaultPolicy, Denial};
+
+mod chmod;
+
+pub use chmod::{parse_mode, ChmodCapable};
+
+mod audit;
+
+pub use audit::{AuditEvent, AuditSink, LoggingAuditSink, NoopAuditSink, Outcome};
+
 bitflags! {
     /// The FTP operations that can be enabled/disabled for the virtual filesystem.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -87,9 +117,26 @@ bitflags! {
         const MD5    = 0b01000000;
         /// If set allows clients to list the contents of a directory.
         const LIST   = 0b10000000;
+        /// If set allows FTP CWD i.e. clients can navigate into a directory.
+        const CWD    = 0b100000000;
+        /// If set allows clients to explicitly retrieve file metadata (e.g. size,
+        /// modification time) via `SIZE`/`MDTM`.
+        ///
+        /// libunftp also calls [`StorageBackend::metadata`](libunftp::storage::StorageBackend::metadata)
+        /// internally while serving `GET`/`LIST`, so [`RestrictingVfs`] permits
+        /// the backing `metadata` call whenever `STAT`, [`GET`](Self::GET) or
+        /// [`LIST`](Self::LIST) is granted — a `GET`- or `LIST`-only user is never
+        /// blocked from downloading or listing just because `STAT` is unset.
+        /// [`READ_OPS`](Self::READ_OPS) bundles all three for users who should be
+        /// able to query metadata directly too.
+        const STAT   = 0b1000000000;
+        /// If set allows the extended SITE CHMOD command to change a file's POSIX permissions.
+        const MODE   = 0b10000000000;
 
         /// Convenience aggragation of all the write operation bits.
         const WRITE_OPS = Self::MK_DIR.bits() | Self::RM_DIR.bits() | Self::PUT.bits() | Self::DEL.bits() | Self::RENAME.bits();
+        /// Convenience aggragation of all the read-only operation bits.
+        const READ_OPS = Self::GET.bits() | Self::LIST.bits() | Self::STAT.bits() | Self::CWD.bits() | Self::MD5.bits();
     }
 }
 
@@ -97,11 +144,30 @@ bitflags! {
 pub trait UserWithPermissions: UserDetail {
     /// Returns the permissions given to the user
     fn permissions(&self) -> VfsOperations;
+
+    /// Returns the user's path-scoped permission rules, if any.
+    ///
+    /// When this returns `Some`, [`RestrictingVfs`] resolves the effective
+    /// [`VfsOperations`] mask for each request from the [`PathRules`] rather than
+    /// from the flat [`permissions`](UserWithPermissions::permissions) mask.
+    /// Defaults to `None`, preserving the original flat-permission behavior.
+    fn path_permissions(&self) -> Option<&PathRules> {
+        None
+    }
+
+    /// Returns a human-readable identifier for this user, recorded in
+    /// [`AuditEvent::username`](crate::AuditEvent::username).
+    ///
+    /// Defaults to a placeholder so adding this method doesn't break existing
+    /// implementors; override it (typically with the FTP login name) to get
+    /// meaningful audit trails.
+    fn username(&self) -> &str {
+        "<unknown>"
+    }
 }
 
 /// A virtual filesystem that checks if the user has permissions to do its operations before it
 /// delegates to another storage back-end.
-#[derive(Debug)]
 pub struct RestrictingVfs<Delegate, User, Meta>
 where
     Delegate: StorageBackend<User>,
@@ -109,24 +175,134 @@ where
     Meta: Metadata + Debug + Sync + Send,
 {
     delegate: Delegate,
+    policy: std::sync::Arc<dyn AuthorizationPolicy<User>>,
+    audit: std::sync::Arc<dyn AuditSink>,
     x: PhantomData<Meta>,
     y: PhantomData<User>,
 }
 
+impl<Delegate, User, Meta> Debug for RestrictingVfs<Delegate, User, Meta>
+where
+    Delegate: StorageBackend<User> + Debug,
+    User: UserWithPermissions,
+    Meta: Metadata + Debug + Sync + Send,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RestrictingVfs")
+            .field("delegate", &self.delegate)
+            .finish()
+    }
+}
+
 impl<Delegate, User, Meta> RestrictingVfs<Delegate, User, Meta>
 where
     Delegate: StorageBackend<User>,
     User: UserWithPermissions,
     Meta: Metadata + Debug + Sync + Send,
 {
-    /// Creates a new instance of [`RestrictingVfs`](crate::RestrictingVfs).
+    /// Creates a new instance of [`RestrictingVfs`](crate::RestrictingVfs), authorizing
+    /// with the [`DefaultPolicy`] and auditing nothing (see [`NoopAuditSink`]).
     pub fn new(delegate: Delegate) -> Self {
         RestrictingVfs {
             delegate,
+            policy: std::sync::Arc::new(DefaultPolicy::new()),
+            audit: std::sync::Arc::new(NoopAuditSink),
             x: PhantomData,
             y: PhantomData,
         }
     }
+
+    /// Replaces the [`AuthorizationPolicy`] consulted for every operation, e.g. to
+    /// layer time-of-day windows, quotas, or a maintenance mode on top of (or
+    /// instead of) the [`DefaultPolicy`].
+    pub fn with_policy<P: AuthorizationPolicy<User> + 'static>(mut self, policy: P) -> Self {
+        self.policy = std::sync::Arc::new(policy);
+        self
+    }
+
+    /// Registers `sink` to observe every allowed and denied operation, e.g. for
+    /// intrusion detection or compliance logging.
+    pub fn with_audit<A: AuditSink + 'static>(mut self, sink: A) -> Self {
+        self.audit = std::sync::Arc::new(sink);
+        self
+    }
+
+    /// Authorizes `op` against `path` via the configured [`AuthorizationPolicy`]
+    /// and reports the decision to the configured [`AuditSink`]. Every
+    /// [`StorageBackend`] method routes through this (or
+    /// [`authorize_paths`](RestrictingVfs::authorize_paths) for `rename` and
+    /// [`authorize_any`](RestrictingVfs::authorize_any) for `metadata`), so the
+    /// decision logic for a given call lives in exactly one place.
+    async fn authorize(&self, user: &User, op: VfsOperations, path: &Path) -> Result<(), Denial> {
+        self.authorize_paths(user, op, &[path]).await
+    }
+
+    /// Like [`authorize`](RestrictingVfs::authorize), but for operations with more
+    /// than one target path (`rename`'s `from` and `to`), reported as a single
+    /// audit event.
+    async fn authorize_paths(&self, user: &User, op: VfsOperations, paths: &[&Path]) -> Result<(), Denial> {
+        let mut verdict = Ok(());
+        for path in paths {
+            verdict = self.policy.authorize(user, op, path).await;
+            if verdict.is_err() {
+                break;
+            }
+        }
+        self.audit.record(AuditEvent {
+            username: user.username().to_string(),
+            operation: op,
+            paths: paths.iter().map(|path| path.to_path_buf()).collect(),
+            outcome: if verdict.is_ok() { Outcome::Allowed } else { Outcome::Denied },
+        });
+        verdict
+    }
+
+    /// Like [`authorize`](RestrictingVfs::authorize), but succeeds if *any* of
+    /// `ops` is authorized, reporting a single audit event for `ops[0]`.
+    ///
+    /// Used by [`metadata`](RestrictingVfs::metadata): libunftp also calls a
+    /// backend's `metadata` internally while serving `GET`/`LIST` (for
+    /// `SIZE`/`MDTM` and transfer setup), so a user granted `GET` or `LIST` but
+    /// not `STAT` must not have those regress.
+    async fn authorize_any(&self, user: &User, ops: &[VfsOperations], path: &Path) -> Result<(), Denial> {
+        let mut verdict = Err(Denial::new());
+        for op in ops {
+            verdict = self.policy.authorize(user, *op, path).await;
+            if verdict.is_ok() {
+                break;
+            }
+        }
+        self.audit.record(AuditEvent {
+            username: user.username().to_string(),
+            operation: ops[0],
+            paths: vec![path.to_path_buf()],
+            outcome: if verdict.is_ok() { Outcome::Allowed } else { Outcome::Denied },
+        });
+        verdict
+    }
+}
+
+impl<Delegate, User, Meta> RestrictingVfs<Delegate, User, Meta>
+where
+    Delegate: ChmodCapable<User>,
+    User: UserWithPermissions,
+    Meta: Metadata + Debug + Sync + Send,
+{
+    /// Sets the POSIX file mode of `path` to `mode` via `SITE CHMOD`, gated on the
+    /// [`VfsOperations::MODE`] permission bit even when the delegate back-end
+    /// supports `chmod`. Use [`parse_mode`] to turn an octal (`0644`) or symbolic
+    /// (`rwxr-x---`) specification into `mode`.
+    pub async fn set_permissions<P: AsRef<Path> + Send + Debug>(
+        &self,
+        user: &User,
+        path: P,
+        mode: u32,
+    ) -> storage::Result<()> {
+        match self.authorize(user, VfsOperations::MODE, path.as_ref()).await {
+            Ok(()) => self.delegate.chmod(user, path, mode).await,
+            Err(denial) => Err(denial.into_storage_error()),
+        }
+    }
 }
 
 #[async_trait]
@@ -151,7 +327,11 @@ where
         user: &User,
         path: P,
     ) -> storage::Result<Self::Metadata> {
-        self.delegate.metadata(user, path).await
+        let ops = [VfsOperations::STAT, VfsOperations::GET, VfsOperations::LIST];
+        match self.authorize_any(user, &ops, path.as_ref()).await {
+            Ok(()) => self.delegate.metadata(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
+        }
     }
 
     async fn md5<P: AsRef<Path> + Send + Debug>(
@@ -162,10 +342,9 @@ where
     where
         P: AsRef<Path> + Send + Debug,
     {
-        if user.permissions().contains(VfsOperations::MD5) {
-            self.delegate.md5(user, path).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::MD5, path.as_ref()).await {
+            Ok(()) => self.delegate.md5(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -177,10 +356,9 @@ where
     where
         <Self as StorageBackend<User>>::Metadata: Metadata,
     {
-        if user.permissions().contains(VfsOperations::LIST) {
-            self.delegate.list(user, path).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::LIST, path.as_ref()).await {
+            Ok(()) => self.delegate.list(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -189,10 +367,9 @@ where
         P: AsRef<Path> + Send + Debug,
         Self::Metadata: Metadata + 'static,
     {
-        if user.permissions().contains(VfsOperations::LIST) {
-            self.delegate.list_fmt(user, path).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::LIST, path.as_ref()).await {
+            Ok(()) => self.delegate.list_fmt(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -201,10 +378,9 @@ where
         P: AsRef<Path> + Send + Debug,
         Self::Metadata: Metadata + 'static,
     {
-        if user.permissions().contains(VfsOperations::LIST) {
-            self.delegate.nlst(user, path).await
-        } else {
-            Err(ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::LIST, path.as_ref()).await {
+            Ok(()) => self.delegate.nlst(user, path).await,
+            Err(denial) => Err(denial.into_io_error()),
         }
     }
 
@@ -219,10 +395,9 @@ where
         W: tokio::io::AsyncWrite + Unpin + Sync + Send,
         P: AsRef<Path> + Send + Debug,
     {
-        if user.permissions().contains(VfsOperations::GET) {
-            self.delegate.get_into(user, path, start_pos, output).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::GET, path.as_ref()).await {
+            Ok(()) => self.delegate.get_into(user, path, start_pos, output).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -232,10 +407,9 @@ where
         path: P,
         start_pos: u64,
     ) -> storage::Result<Box<dyn AsyncRead + Send + Sync + Unpin>> {
-        if user.permissions().contains(VfsOperations::GET) {
-            self.delegate.get(user, path, start_pos).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::GET, path.as_ref()).await {
+            Ok(()) => self.delegate.get(user, path, start_pos).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -249,10 +423,9 @@ where
         path: P,
         start_pos: u64,
     ) -> storage::Result<u64> {
-        if user.permissions().contains(VfsOperations::PUT) {
-            self.delegate.put(user, input, path, start_pos).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::PUT, path.as_ref()).await {
+            Ok(()) => self.delegate.put(user, input, path, start_pos).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -261,10 +434,9 @@ where
         user: &User,
         path: P,
     ) -> storage::Result<()> {
-        if user.permissions().contains(VfsOperations::DEL) {
-            self.delegate.del(user, path).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::DEL, path.as_ref()).await {
+            Ok(()) => self.delegate.del(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -273,10 +445,9 @@ where
         user: &User,
         path: P,
     ) -> storage::Result<()> {
-        if user.permissions().contains(VfsOperations::MK_DIR) {
-            self.delegate.mkd(user, path).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::MK_DIR, path.as_ref()).await {
+            Ok(()) => self.delegate.mkd(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -286,10 +457,12 @@ where
         from: P,
         to: P,
     ) -> storage::Result<()> {
-        if user.permissions().contains(VfsOperations::RENAME) {
-            self.delegate.rename(user, from, to).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self
+            .authorize_paths(user, VfsOperations::RENAME, &[from.as_ref(), to.as_ref()])
+            .await
+        {
+            Ok(()) => self.delegate.rename(user, from, to).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -298,10 +471,9 @@ where
         user: &User,
         path: P,
     ) -> storage::Result<()> {
-        if user.permissions().contains(VfsOperations::RM_DIR) {
-            self.delegate.rmd(user, path).await
-        } else {
-            Err(libunftp::storage::ErrorKind::PermissionDenied.into())
+        match self.authorize(user, VfsOperations::RM_DIR, path.as_ref()).await {
+            Ok(()) => self.delegate.rmd(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
         }
     }
 
@@ -310,6 +482,221 @@ where
         user: &User,
         path: P,
     ) -> storage::Result<()> {
-        self.delegate.cwd(user, path).await
+        match self.authorize(user, VfsOperations::CWD, path.as_ref()).await {
+            Ok(()) => self.delegate.cwd(user, path).await,
+            Err(denial) => Err(denial.into_storage_error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Clone, Copy)]
+    struct StubMeta;
+
+    impl Metadata for StubMeta {
+        fn len(&self) -> u64 {
+            0
+        }
+
+        fn is_dir(&self) -> bool {
+            false
+        }
+
+        fn is_file(&self) -> bool {
+            true
+        }
+
+        fn is_symlink(&self) -> bool {
+            false
+        }
+
+        fn modified(&self) -> storage::Result<std::time::SystemTime> {
+            Ok(std::time::SystemTime::UNIX_EPOCH)
+        }
+
+        fn gid(&self) -> u32 {
+            0
+        }
+
+        fn uid(&self) -> u32 {
+            0
+        }
+    }
+
+    #[derive(Debug)]
+    struct TestUser {
+        path_permissions: PathRules,
+    }
+
+    impl UserDetail for TestUser {
+        fn account_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    impl std::fmt::Display for TestUser {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "TestUser")
+        }
+    }
+
+    impl UserWithPermissions for TestUser {
+        fn permissions(&self) -> VfsOperations {
+            VfsOperations::empty()
+        }
+
+        fn path_permissions(&self) -> Option<&PathRules> {
+            Some(&self.path_permissions)
+        }
+    }
+
+    /// A [`StorageBackend`] stub that records how many times `rename` was
+    /// actually invoked, so tests can assert it's never reached when
+    /// authorization fails.
+    #[derive(Default)]
+    struct StubDelegate {
+        rename_calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl StorageBackend<TestUser> for StubDelegate {
+        type Metadata = StubMeta;
+
+        fn name(&self) -> &str {
+            "stub"
+        }
+
+        fn supported_features(&self) -> u32 {
+            0
+        }
+
+        async fn metadata<P: AsRef<Path> + Send + Debug>(
+            &self,
+            _user: &TestUser,
+            _path: P,
+        ) -> storage::Result<Self::Metadata> {
+            Ok(StubMeta)
+        }
+
+        async fn md5<P: AsRef<Path> + Send + Debug>(&self, _user: &TestUser, _path: P) -> storage::Result<String>
+        where
+            P: AsRef<Path> + Send + Debug,
+        {
+            Ok(String::new())
+        }
+
+        async fn list<P: AsRef<Path> + Send + Debug>(
+            &self,
+            _user: &TestUser,
+            _path: P,
+        ) -> storage::Result<Vec<Fileinfo<PathBuf, Self::Metadata>>>
+        where
+            <Self as StorageBackend<TestUser>>::Metadata: Metadata,
+        {
+            Ok(Vec::new())
+        }
+
+        async fn list_fmt<P>(&self, _user: &TestUser, _path: P) -> storage::Result<Cursor<Vec<u8>>>
+        where
+            P: AsRef<Path> + Send + Debug,
+            Self::Metadata: Metadata + 'static,
+        {
+            Ok(Cursor::new(Vec::new()))
+        }
+
+        async fn nlst<P>(&self, _user: &TestUser, _path: P) -> std::result::Result<Cursor<Vec<u8>>, Error>
+        where
+            P: AsRef<Path> + Send + Debug,
+            Self::Metadata: Metadata + 'static,
+        {
+            Ok(Cursor::new(Vec::new()))
+        }
+
+        async fn get_into<'a, P, W: ?Sized>(
+            &self,
+            _user: &TestUser,
+            _path: P,
+            _start_pos: u64,
+            _output: &'a mut W,
+        ) -> storage::Result<u64>
+        where
+            W: tokio::io::AsyncWrite + Unpin + Sync + Send,
+            P: AsRef<Path> + Send + Debug,
+        {
+            Ok(0)
+        }
+
+        async fn get<P: AsRef<Path> + Send + Debug>(
+            &self,
+            _user: &TestUser,
+            _path: P,
+            _start_pos: u64,
+        ) -> storage::Result<Box<dyn AsyncRead + Send + Sync + Unpin>> {
+            Ok(Box::new(Cursor::new(Vec::new())))
+        }
+
+        async fn put<P: AsRef<Path> + Send + Debug, R: tokio::io::AsyncRead + Send + Sync + Unpin + 'static>(
+            &self,
+            _user: &TestUser,
+            _input: R,
+            _path: P,
+            _start_pos: u64,
+        ) -> storage::Result<u64> {
+            Ok(0)
+        }
+
+        async fn del<P: AsRef<Path> + Send + Debug>(&self, _user: &TestUser, _path: P) -> storage::Result<()> {
+            Ok(())
+        }
+
+        async fn mkd<P: AsRef<Path> + Send + Debug>(&self, _user: &TestUser, _path: P) -> storage::Result<()> {
+            Ok(())
+        }
+
+        async fn rename<P: AsRef<Path> + Send + Debug>(&self, _user: &TestUser, _from: P, _to: P) -> storage::Result<()> {
+            self.rename_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn rmd<P: AsRef<Path> + Send + Debug>(&self, _user: &TestUser, _path: P) -> storage::Result<()> {
+            Ok(())
+        }
+
+        async fn cwd<P: AsRef<Path> + Send + Debug>(&self, _user: &TestUser, _path: P) -> storage::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn rename_denies_when_either_path_lacks_permission() {
+        let user = TestUser {
+            path_permissions: PathRules::new(VfsOperations::empty()).rule("/incoming/**", VfsOperations::RENAME),
+        };
+        let vfs = RestrictingVfs::<StubDelegate, TestUser, StubMeta>::new(StubDelegate::default());
+
+        let result = vfs.rename(&user, "/incoming/a.txt", "/archive/b.txt").await;
+
+        assert!(result.is_err(), "rename must be denied when the destination lacks RENAME permission");
+        assert_eq!(
+            vfs.delegate.rename_calls.load(Ordering::SeqCst),
+            0,
+            "the delegate's rename must not be reached when authorization fails"
+        );
+    }
+
+    #[tokio::test]
+    async fn rename_allows_when_both_paths_have_permission() {
+        let user = TestUser {
+            path_permissions: PathRules::new(VfsOperations::empty()).rule("/incoming/**", VfsOperations::RENAME),
+        };
+        let vfs = RestrictingVfs::<StubDelegate, TestUser, StubMeta>::new(StubDelegate::default());
+
+        let result = vfs.rename(&user, "/incoming/a.txt", "/incoming/b.txt").await;
+
+        assert!(result.is_ok(), "rename must be allowed when both paths have RENAME permission");
     }
 }
@@ -0,0 +1,169 @@
+//! Path-scoped permission rules, allowing a user's [`VfsOperations`](crate::VfsOperations)
+//! mask to vary by directory instead of applying uniformly across the whole virtual
+//! filesystem.
+
+use crate::VfsOperations;
+use std::path::Path;
+
+/// A compiled path pattern such as `/incoming/**` or `/archive/*`.
+///
+/// Patterns are matched against the `/`-separated segments of the target path:
+/// a `*` segment matches exactly one path segment, a `**` segment matches zero
+/// or more segments, and any other segment must match literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Exact(String),
+    Star,
+    DoubleStar,
+}
+
+impl Pattern {
+    fn compile(raw: &str) -> Self {
+        let segments = raw
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| match s {
+                "**" => Segment::DoubleStar,
+                "*" => Segment::Star,
+                other => Segment::Exact(other.to_string()),
+            })
+            .collect();
+        Pattern {
+            raw: raw.to_string(),
+            segments,
+        }
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        // Segment the path the same way `compile` segments the pattern (split on
+        // `/`, drop empty segments) rather than via `Path::components`, which
+        // would keep a leading `RootDir` as its own `"/"` segment and make every
+        // absolute-path pattern fail to match.
+        let path = path.to_string_lossy();
+        let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        Self::matches_from(&self.segments, &path_segments)
+    }
+
+    fn matches_from(pattern: &[Segment], path: &[&str]) -> bool {
+        match pattern.first() {
+            None => path.is_empty(),
+            Some(Segment::Exact(expected)) => match path.first() {
+                Some(actual) if actual == expected => Self::matches_from(&pattern[1..], &path[1..]),
+                _ => false,
+            },
+            Some(Segment::Star) => match path.first() {
+                Some(_) => Self::matches_from(&pattern[1..], &path[1..]),
+                None => false,
+            },
+            Some(Segment::DoubleStar) => {
+                // `**` matches zero or more segments, so try every split point.
+                if Self::matches_from(&pattern[1..], path) {
+                    return true;
+                }
+                match path.first() {
+                    Some(_) => Self::matches_from(pattern, &path[1..]),
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+/// An ordered, per-directory permission ACL.
+///
+/// Each entry pairs a glob-like [pattern](Pattern) with the [`VfsOperations`] mask
+/// granted to paths it matches. Rules are evaluated in the order they were added
+/// and the **last matching rule wins**; if no rule matches, `default` applies.
+/// This means more specific overrides should be added after more general rules
+/// (e.g. add `/incoming/**` before `/incoming/private/**`).
+#[derive(Debug, Clone)]
+pub struct PathRules {
+    rules: Vec<(Pattern, VfsOperations)>,
+    default: VfsOperations,
+}
+
+impl PathRules {
+    /// Creates an empty rule set that resolves every path to `default`.
+    pub fn new(default: VfsOperations) -> Self {
+        PathRules {
+            rules: Vec::new(),
+            default,
+        }
+    }
+
+    /// Appends a rule mapping `pattern` to `ops`, taking precedence over every
+    /// previously added rule that also matches.
+    pub fn rule(mut self, pattern: &str, ops: VfsOperations) -> Self {
+        self.rules.push((Pattern::compile(pattern), ops));
+        self
+    }
+
+    /// Resolves the effective [`VfsOperations`] mask for `path`, i.e. the mask of
+    /// the last matching rule, or `default` if nothing matches.
+    pub fn resolve(&self, path: &Path) -> VfsOperations {
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(path))
+            .last()
+            .map(|(_, ops)| *ops)
+            .unwrap_or(self.default)
+    }
+
+    /// The patterns configured on this rule set, in evaluation order, most
+    /// recently added (and therefore highest priority on a tie) last.
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|(pattern, _)| pattern.raw.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn falls_back_to_default_when_nothing_matches() {
+        let rules = PathRules::new(VfsOperations::GET);
+        assert_eq!(rules.resolve(Path::new("/etc/passwd")), VfsOperations::GET);
+    }
+
+    #[test]
+    fn matches_absolute_paths() {
+        let rules = PathRules::new(VfsOperations::GET).rule("/incoming/**", VfsOperations::PUT);
+        assert_eq!(rules.resolve(Path::new("/incoming/file.txt")), VfsOperations::PUT);
+        assert_eq!(rules.resolve(Path::new("/incoming/sub/file.txt")), VfsOperations::PUT);
+        assert_eq!(rules.resolve(Path::new("/other/file.txt")), VfsOperations::GET);
+    }
+
+    #[test]
+    fn double_star_matches_zero_segments() {
+        let rules = PathRules::new(VfsOperations::empty()).rule("/incoming/**", VfsOperations::PUT);
+        assert_eq!(rules.resolve(Path::new("/incoming")), VfsOperations::PUT);
+    }
+
+    #[test]
+    fn star_matches_exactly_one_segment() {
+        let rules = PathRules::new(VfsOperations::empty()).rule("/incoming/*", VfsOperations::PUT);
+        assert_eq!(rules.resolve(Path::new("/incoming/file.txt")), VfsOperations::PUT);
+        assert_eq!(rules.resolve(Path::new("/incoming/sub/file.txt")), VfsOperations::empty());
+        assert_eq!(rules.resolve(Path::new("/incoming")), VfsOperations::empty());
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules = PathRules::new(VfsOperations::empty())
+            .rule("/incoming/**", VfsOperations::PUT)
+            .rule("/incoming/private/**", VfsOperations::empty());
+        assert_eq!(rules.resolve(Path::new("/incoming/file.txt")), VfsOperations::PUT);
+        assert_eq!(
+            rules.resolve(Path::new("/incoming/private/secret.txt")),
+            VfsOperations::empty()
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! A pluggable [`AuthorizationPolicy`] extension point, replacing the permission
+//! checks that used to be hard-coded into every [`StorageBackend`](libunftp::storage::StorageBackend)
+//! method.
+
+use crate::{UserWithPermissions, VfsOperations};
+use async_trait::async_trait;
+use std::marker::PhantomData;
+use std::path::Path;
+
+#[cfg(feature = "http-policy")]
+use crate::{http_policy, CachedPermissionProvider};
+
+/// Why an operation was denied.
+///
+/// Carries an optional human-readable explanation so a custom
+/// [`AuthorizationPolicy`] can describe *why* access was refused (e.g. "outside
+/// business hours", "quota exceeded") rather than a bare permission-denied error.
+#[derive(Debug, Clone, Default)]
+pub struct Denial {
+    message: Option<String>,
+}
+
+impl Denial {
+    /// Creates a denial with no explanatory message.
+    pub fn new() -> Self {
+        Denial::default()
+    }
+
+    /// Creates a denial carrying a human-readable explanation.
+    pub fn with_message(message: impl Into<String>) -> Self {
+        Denial {
+            message: Some(message.into()),
+        }
+    }
+
+    /// The explanation given for the denial, if any.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub(crate) fn into_storage_error(self) -> libunftp::storage::Error {
+        match self.message {
+            Some(message) => libunftp::storage::Error::new(libunftp::storage::ErrorKind::PermissionDenied, message),
+            None => libunftp::storage::ErrorKind::PermissionDenied.into(),
+        }
+    }
+
+    pub(crate) fn into_io_error(self) -> std::io::Error {
+        match self.message {
+            Some(message) => std::io::Error::new(std::io::ErrorKind::PermissionDenied, message),
+            None => std::io::ErrorKind::PermissionDenied.into(),
+        }
+    }
+}
+
+/// Decides whether `user` may perform `op` against `path`.
+///
+/// Implement this to add cross-cutting authorization logic (time-of-day windows,
+/// quotas, a read-only maintenance mode, dry-run auditing) that a flat
+/// permission mask can't express. Install a custom implementation with
+/// [`RestrictingVfs::with_policy`](crate::RestrictingVfs::with_policy); the
+/// original flag-containment behavior ships as [`DefaultPolicy`].
+#[async_trait]
+pub trait AuthorizationPolicy<User>: Send + Sync
+where
+    User: UserWithPermissions,
+{
+    /// Returns `Ok(())` if `user` may perform `op` against `path`, or
+    /// `Err(Denial)` explaining why not.
+    async fn authorize(&self, user: &User, op: VfsOperations, path: &Path) -> Result<(), Denial>;
+}
+
+/// The built-in [`AuthorizationPolicy`]: consults the user's [`PathRules`](crate::PathRules) (if
+/// any, falling back to their flat [`permissions`](UserWithPermissions::permissions)),
+/// and, when configured via [`with_remote_permissions`](DefaultPolicy::with_remote_permissions),
+/// an external [`HttpPermissionProvider`](crate::HttpPermissionProvider) that takes
+/// precedence over both.
+pub struct DefaultPolicy<User> {
+    y: PhantomData<User>,
+    #[cfg(feature = "http-policy")]
+    remote_permissions: Option<http_policy::RemoteProviderHandle<User>>,
+}
+
+impl<User> Default for DefaultPolicy<User> {
+    fn default() -> Self {
+        DefaultPolicy {
+            y: PhantomData,
+            #[cfg(feature = "http-policy")]
+            remote_permissions: None,
+        }
+    }
+}
+
+impl<User> DefaultPolicy<User> {
+    /// Creates a policy that authorizes purely from the user's local permissions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn effective_permissions(&self, user: &User, path: &Path) -> VfsOperations
+    where
+        User: UserWithPermissions,
+    {
+        #[cfg(feature = "http-policy")]
+        if let Some(handle) = &self.remote_permissions {
+            if let Some(resolved) = handle.0.resolve_for(user).await {
+                return match resolved.rules {
+                    Some(rules) => rules.resolve(path),
+                    None => resolved.permissions,
+                };
+            }
+        }
+        match user.path_permissions() {
+            Some(rules) => rules.resolve(path),
+            None => user.permissions(),
+        }
+    }
+}
+
+#[cfg(feature = "http-policy")]
+impl<User: http_policy::NamedUser> DefaultPolicy<User> {
+    /// Has this policy consult `provider` for each request, taking precedence
+    /// over the user's local permissions.
+    pub fn with_remote_permissions(mut self, provider: CachedPermissionProvider) -> Self {
+        self.remote_permissions = Some(http_policy::RemoteProviderHandle(std::sync::Arc::new(provider)));
+        self
+    }
+}
+
+impl<User> std::fmt::Debug for DefaultPolicy<User> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DefaultPolicy").finish()
+    }
+}
+
+#[async_trait]
+impl<User: UserWithPermissions> AuthorizationPolicy<User> for DefaultPolicy<User> {
+    async fn authorize(&self, user: &User, op: VfsOperations, path: &Path) -> Result<(), Denial> {
+        if self.effective_permissions(user, path).await.contains(op) {
+            Ok(())
+        } else {
+            Err(Denial::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PathRules;
+    use libunftp::auth::UserDetail;
+
+    #[derive(Debug)]
+    struct TestUser {
+        permissions: VfsOperations,
+        path_permissions: Option<PathRules>,
+    }
+
+    impl UserDetail for TestUser {
+        fn account_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    impl std::fmt::Display for TestUser {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "TestUser")
+        }
+    }
+
+    impl UserWithPermissions for TestUser {
+        fn permissions(&self) -> VfsOperations {
+            self.permissions
+        }
+
+        fn path_permissions(&self) -> Option<&PathRules> {
+            self.path_permissions.as_ref()
+        }
+    }
+
+    #[tokio::test]
+    async fn gates_on_the_stat_cwd_and_mode_bits() {
+        let user = TestUser {
+            permissions: VfsOperations::GET | VfsOperations::CWD,
+            path_permissions: None,
+        };
+        let policy = DefaultPolicy::new();
+
+        assert!(policy.authorize(&user, VfsOperations::CWD, Path::new("/")).await.is_ok());
+        assert!(policy.authorize(&user, VfsOperations::STAT, Path::new("/")).await.is_err());
+        assert!(policy.authorize(&user, VfsOperations::MODE, Path::new("/")).await.is_err());
+    }
+
+    // The rename two-path check (both `from` and `to` must be authorized) is
+    // exercised end-to-end against the real `RestrictingVfs::rename` in
+    // `crate::tests`, since that's where the check actually lives.
+}